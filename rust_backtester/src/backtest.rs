@@ -0,0 +1,558 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use crate::binfmt::{self, PnlRecord, RecordKind, ScoreRecord};
+use crate::errors::{Mode, OnError, RowError, SkipCounter};
+use crate::fastparse::{self, Progress};
+use crate::window::WeightedMeanWindow;
+
+/// Fraction of names (by rank) that go long / short each rebalance date.
+const QUANTILE: f64 = 0.2;
+
+/// Default relative weight on the momentum vs. reversal z-score in the
+/// composite signal, overridable via `--w-mom`/`--w-rev`.
+const W_MOM: f64 = 0.5;
+const W_REV: f64 = 0.5;
+
+#[derive(Deserialize)]
+struct ReplayRow {
+    portfolio: f64,
+}
+
+#[derive(Deserialize)]
+struct RustRow {
+    #[serde(rename = "mom_score")]
+    mom_score: f64,
+    #[serde(rename = "rev_score")]
+    rev_score: f64,
+    // Deserialized to validate the column is present, but the backtest
+    // itself only needs TradeDate for rebalance grouping.
+    #[serde(rename = "Announced")]
+    #[allow(dead_code)]
+    announced: String,
+    #[serde(rename = "TradeDate")]
+    trade_date: String,
+    #[serde(rename = "Ticker")]
+    ticker: String,
+    price: f64,
+}
+
+/// One row of the wide input, grouped by rebalance date for the event study.
+/// Built from either a CSV `RustRow` or a binary `ScoreRecord`, so
+/// `run_backtest` doesn't care which `--input-format` produced it.
+struct ScoredRow {
+    ticker: String,
+    trade_date: String,
+    mom_score: f64,
+    rev_score: f64,
+    price: f64,
+}
+
+impl From<RustRow> for ScoredRow {
+    fn from(row: RustRow) -> Self {
+        ScoredRow {
+            ticker: row.ticker,
+            trade_date: row.trade_date,
+            mom_score: row.mom_score,
+            rev_score: row.rev_score,
+            price: row.price,
+        }
+    }
+}
+
+impl From<ScoreRecord> for ScoredRow {
+    fn from(rec: ScoreRecord) -> Self {
+        ScoredRow {
+            ticker: rec.ticker_str().to_string(),
+            trade_date: rec.trade_date_str().to_string(),
+            mom_score: rec.mom_score,
+            rev_score: rec.rev_score,
+            price: rec.price,
+        }
+    }
+}
+
+impl TryFrom<&ScoredRow> for ScoreRecord {
+    type Error = Box<dyn Error>;
+
+    fn try_from(row: &ScoredRow) -> Result<Self, Self::Error> {
+        ScoreRecord::new(
+            &row.ticker,
+            &row.trade_date,
+            row.mom_score,
+            row.rev_score,
+            row.price,
+        )
+    }
+}
+
+/// Cross-sectional mean/sample-std of a slice of scores, guarding a
+/// degenerate (zero) sigma. Uses Bessel's correction (`n - 1`) so a single
+/// score (`n == 1`) falls back to `n` rather than dividing by zero.
+fn mean_std(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len() as f64;
+    let mu = xs.iter().sum::<f64>() / n;
+    let var = xs.iter().map(|x| (x - mu).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+    (mu, var.sqrt())
+}
+
+fn zscore(x: f64, mu: f64, sigma: f64) -> f64 {
+    if sigma == 0.0 {
+        0.0
+    } else {
+        (x - mu) / sigma
+    }
+}
+
+/// Runs the event-study backtest: rank the composite signal within each
+/// `TradeDate`, go long the top quantile and short the bottom quantile, and
+/// mark each position to its ticker's next available price. Returns one
+/// `PnlRecord` per rebalance date with a forward period to mark to.
+/// `w_mom`/`w_rev` weight the momentum vs. reversal z-score in the
+/// composite signal.
+fn run_backtest(rows: Vec<ScoredRow>, w_mom: f64, w_rev: f64) -> Result<Vec<PnlRecord>, Box<dyn Error>> {
+    // 1) Group rows by rebalance date, preserving first-seen date order.
+    let mut date_order: Vec<String> = Vec::new();
+    let mut by_date: HashMap<String, Vec<ScoredRow>> = HashMap::new();
+    for row in rows {
+        by_date.entry(row.trade_date.clone()).or_insert_with(|| {
+            date_order.push(row.trade_date.clone());
+            Vec::new()
+        });
+        by_date.get_mut(&row.trade_date).unwrap().push(row);
+    }
+
+    // 2) Build a ticker -> price lookup per date so we can find each
+    //    position's forward (next rebalance) price.
+    let prices_by_date: HashMap<&str, HashMap<&str, f64>> = date_order
+        .iter()
+        .map(|d| {
+            let m = by_date[d]
+                .iter()
+                .map(|r| (r.ticker.as_str(), r.price))
+                .collect();
+            (d.as_str(), m)
+        })
+        .collect();
+
+    let mut equity = 1.0;
+    let mut records = Vec::new();
+    for (i, date) in date_order.iter().enumerate() {
+        let Some(next_date) = date_order.get(i + 1) else {
+            // No forward period to mark the last rebalance to; skip it.
+            continue;
+        };
+        let next_prices = &prices_by_date[next_date.as_str()];
+
+        let group = &by_date[date];
+        let mom_scores: Vec<f64> = group.iter().map(|r| r.mom_score).collect();
+        let rev_scores: Vec<f64> = group.iter().map(|r| r.rev_score).collect();
+        let (mom_mu, mom_sigma) = mean_std(&mom_scores);
+        let (rev_mu, rev_sigma) = mean_std(&rev_scores);
+
+        // 3) Composite signal = weighted sum of the two z-scored signals.
+        let mut ranked: Vec<(f64, &ScoredRow)> = group
+            .iter()
+            .map(|r| {
+                let signal = w_mom * zscore(r.mom_score, mom_mu, mom_sigma)
+                    + w_rev * zscore(r.rev_score, rev_mu, rev_sigma);
+                (signal, r)
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let n_quantile = ((ranked.len() as f64) * QUANTILE).round().max(1.0) as usize;
+        let shorts = &ranked[..n_quantile.min(ranked.len())];
+        let longs = &ranked[ranked.len().saturating_sub(n_quantile)..];
+
+        // 4) Forward return per position, equal-weighted long minus short.
+        let forward_return = |r: &ScoredRow| -> Option<f64> {
+            next_prices
+                .get(r.ticker.as_str())
+                .map(|&next_price| (next_price / r.price) - 1.0)
+        };
+
+        let long_rets: Vec<f64> = longs.iter().filter_map(|(_, r)| forward_return(r)).collect();
+        let short_rets: Vec<f64> = shorts.iter().filter_map(|(_, r)| forward_return(r)).collect();
+
+        let long_pnl = if long_rets.is_empty() {
+            0.0
+        } else {
+            long_rets.iter().sum::<f64>() / long_rets.len() as f64
+        };
+        let short_pnl = if short_rets.is_empty() {
+            0.0
+        } else {
+            short_rets.iter().sum::<f64>() / short_rets.len() as f64
+        };
+        let period_pnl = long_pnl - short_pnl;
+        equity *= 1.0 + period_pnl;
+
+        records.push(PnlRecord::new(date, period_pnl, equity)?);
+    }
+
+    Ok(records)
+}
+
+/// Parses an optional `--smooth-window <seconds>` flag out of the trailing
+/// args. Returns `None` when it isn't present.
+fn parse_smooth_window(args: &[String]) -> Result<Option<f64>, Box<dyn Error>> {
+    args.iter()
+        .position(|a| a == "--smooth-window")
+        .and_then(|i| args.get(i + 1))
+        .map(|v| {
+            v.parse()
+                .map_err(|e| format!("--smooth-window expects a number of seconds, got {v:?}: {e}").into())
+        })
+        .transpose()
+}
+
+/// Parses a `--<name> {csv,bin}` flag out of the trailing args, defaulting
+/// to `"csv"` when it isn't present.
+fn parse_format(args: &[String], name: &str) -> String {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+        .unwrap_or_else(|| "csv".to_string())
+}
+
+/// Parses a `--<name> <f64>` flag out of the trailing args, defaulting to
+/// `default` when it isn't present.
+fn parse_weight(args: &[String], name: &str, default: f64) -> Result<f64, Box<dyn Error>> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(|v| {
+            v.parse()
+                .map_err(|e| format!("{name} expects a number, got {v:?}: {e}").into())
+        })
+        .unwrap_or(Ok(default))
+}
+
+/// Reads the `RustRow`s that feed "REAL P&L MODE" from either CSV (the
+/// default) or a memory-mapped `--input-format bin` file of pre-parsed
+/// `ScoreRecord`s, skipping CSV tokenization entirely on the fast path.
+fn read_score_rows(
+    rdr: &mut csv::Reader<BufReader<File>>,
+    input_path: &str,
+    input_format: &str,
+    on_error: OnError,
+    skip_counter: &mut SkipCounter,
+) -> Result<Vec<ScoredRow>, Box<dyn Error>> {
+    match input_format {
+        "bin" => {
+            let record_size = bincode::serialized_size(&ScoreRecord::new("", "", 0.0, 0.0, 0.0)?)? as usize;
+            Ok(
+                binfmt::read_bin::<ScoreRecord>(input_path, RecordKind::Score, record_size)?
+                    .into_iter()
+                    .map(ScoredRow::from)
+                    .collect(),
+            )
+        }
+        "csv" => {
+            let mut progress = Progress::new("pnl-parse");
+            let mut rows = Vec::new();
+            let headers = rdr.headers()?.clone();
+            for (i, result) in rdr.records().enumerate() {
+                let record_number = i as u64 + 1;
+                let record = result?;
+                match record.deserialize::<RustRow>(Some(&headers)) {
+                    Ok(row) => rows.push(ScoredRow::from(row)),
+                    Err(source) => {
+                        let err = RowError::new(record_number, Mode::Pnl, format!("{record:?}"), source);
+                        match on_error {
+                            OnError::Fail => return Err(Box::new(err)),
+                            OnError::Skip => skip_counter.record(&err),
+                        }
+                    }
+                }
+                progress.tick();
+            }
+            progress.finish();
+            Ok(rows)
+        }
+        other => Err(format!("unsupported --input-format {other}").into()),
+    }
+}
+
+/// Converts a `TradeDate` to epoch seconds, accepting either a full RFC3339
+/// timestamp or a bare `YYYY-MM-DD` date (midnight UTC) — `run_backtest`
+/// treats `TradeDate` as an opaque grouping key and accepts either, so
+/// smoothing must too rather than hard-erroring on the plain-date form.
+fn trade_date_epoch_secs(trade_date: &str) -> Result<f64, Box<dyn Error>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(trade_date) {
+        return Ok(dt.timestamp() as f64);
+    }
+    chrono::NaiveDate::parse_from_str(trade_date, "%Y-%m-%d")
+        .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() as f64)
+        .map_err(|e| format!("smooth_scores: invalid TradeDate {trade_date:?}: {e}").into())
+}
+
+/// Runs each score through a per-ticker [`WeightedMeanWindow`] (unit weight
+/// per sample, `TradeDate` converted to epoch seconds for the sample
+/// timestamp) to denoise it before it feeds the composite signal. Keeping
+/// one window per ticker keeps the smoothing from blending unrelated names
+/// together just because they happen to sit next to each other in file
+/// order.
+fn smooth_scores(rows: &mut [ScoredRow], window_duration: f64) -> Result<(), Box<dyn Error>> {
+    let mut windows: HashMap<String, (WeightedMeanWindow, WeightedMeanWindow)> = HashMap::new();
+    for row in rows.iter_mut() {
+        let ts = trade_date_epoch_secs(&row.trade_date)?;
+        let (mom_window, rev_window) = windows.entry(row.ticker.clone()).or_insert_with(|| {
+            (
+                WeightedMeanWindow::new(window_duration),
+                WeightedMeanWindow::new(window_duration),
+            )
+        });
+        mom_window.push(ts, row.mom_score, 1.0);
+        rev_window.push(ts, row.rev_score, 1.0);
+        row.mom_score = mom_window.mean();
+        row.rev_score = rev_window.mean();
+    }
+    Ok(())
+}
+
+/// Entry point for the `backtest` subcommand: `backtest --input <in.csv>
+/// --output <out.csv> [--smooth-window <s>] [--input-format {csv,bin}]
+/// [--output-format {csv,bin}] [--fast] [--on-error {skip,fail}]
+/// [--cache-scores <path>] [--w-mom <weight>] [--w-rev <weight>]`.
+///
+/// `--cache-scores <path>` writes the freshly-parsed `ScoreRecord`s to a
+/// binary cache after a CSV parse, so a later re-run (while iterating on
+/// scoring weights) can point `--input` at that file with
+/// `--input-format bin` and skip CSV tokenization entirely.
+///
+/// `--w-mom`/`--w-rev` override the momentum/reversal weight in the
+/// composite signal, defaulting to [`W_MOM`]/[`W_REV`].
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    if args.len() < 4 || args[0] != "--input" || args[2] != "--output" {
+        return Err("Usage: backtest --input <in.csv> --output <out.csv> \
+             [--smooth-window <seconds>] [--input-format {csv,bin}] \
+             [--output-format {csv,bin}] [--fast] [--on-error {skip,fail}] \
+             [--cache-scores <path>] [--w-mom <weight>] [--w-rev <weight>]"
+            .into());
+    }
+    let input_path = &args[1];
+    let output_path = &args[3];
+    let smooth_window = parse_smooth_window(&args[4..])?;
+    let input_format = parse_format(&args[4..], "--input-format");
+    let output_format = parse_format(&args[4..], "--output-format");
+    let fast = args[4..].iter().any(|a| a == "--fast");
+    let on_error = OnError::parse(&args[4..])?;
+    let w_mom = parse_weight(&args[4..], "--w-mom", W_MOM)?;
+    let w_rev = parse_weight(&args[4..], "--w-rev", W_REV)?;
+    let cache_scores_path = args[4..]
+        .iter()
+        .position(|a| a == "--cache-scores")
+        .and_then(|i| args[4..].get(i + 1))
+        .cloned();
+    let mut skip_counter = SkipCounter::default();
+
+    // 1) Open the input CSV. Binary input (`--input-format bin`) is only
+    //    meaningful for REAL P&L MODE, since REPLAY MODE's `portfolio`
+    //    column is already a single scalar per row, so it skips straight
+    //    past the CSV header sniff below.
+    let input_file = File::open(Path::new(input_path))?;
+    let mut rdr = csv::Reader::from_reader(BufReader::new(input_file));
+
+    // 2) Inspect headers to choose mode.
+    let is_replay_mode = input_format == "csv" && rdr.headers()?.iter().any(|h| h == "portfolio");
+    if is_replay_mode {
+        // === REPLAY MODE ===
+        let output_file = File::create(Path::new(output_path))?;
+        let mut wtr = csv::Writer::from_writer(BufWriter::new(output_file));
+        wtr.write_record(["pnl"])?;
+        let mut progress = Progress::new("replay");
+        if fast {
+            // Fast path: skip full StringRecord/Serde deserialization and
+            // pull the `portfolio` column straight out of each ByteRecord.
+            let headers = rdr.byte_headers()?.clone();
+            let portfolio_col = fastparse::find_column(&headers, "portfolio")
+                .ok_or("replay CSV is missing a portfolio column")?;
+            let mut record = csv::ByteRecord::new();
+            let mut record_number = 0u64;
+            while rdr.read_byte_record(&mut record)? {
+                record_number += 1;
+                match fastparse::parse_f64_column(&record, portfolio_col) {
+                    Ok(portfolio) => wtr.serialize((portfolio,))?,
+                    Err(source) => match on_error {
+                        OnError::Fail => {
+                            return Err(format!(
+                                "row {record_number} (replay mode) failed to parse: {source}"
+                            )
+                            .into())
+                        }
+                        OnError::Skip => {
+                            skip_counter.skipped += 1;
+                            eprintln!("skipping: row {record_number} failed to parse: {source}");
+                        }
+                    },
+                }
+                progress.tick();
+            }
+        } else {
+            let headers = rdr.headers()?.clone();
+            for (i, result) in rdr.records().enumerate() {
+                let record_number = i as u64 + 1;
+                let record = result?;
+                match record.deserialize::<ReplayRow>(Some(&headers)) {
+                    Ok(row) => wtr.serialize((row.portfolio,))?,
+                    Err(source) => {
+                        let err = RowError::new(record_number, Mode::Replay, format!("{record:?}"), source);
+                        match on_error {
+                            OnError::Fail => return Err(Box::new(err)),
+                            OnError::Skip => skip_counter.record(&err),
+                        }
+                    }
+                }
+                progress.tick();
+            }
+        }
+        progress.finish();
+        skip_counter.report();
+        wtr.flush()?;
+    } else {
+        // === REAL P&L MODE ===
+        let mut rows = read_score_rows(
+            &mut rdr,
+            input_path,
+            &input_format,
+            on_error,
+            &mut skip_counter,
+        )?;
+        skip_counter.report();
+        if let Some(cache_path) = &cache_scores_path {
+            let score_records: Vec<ScoreRecord> = rows
+                .iter()
+                .map(ScoreRecord::try_from)
+                .collect::<Result<_, _>>()?;
+            binfmt::write_bin(cache_path, RecordKind::Score, &score_records)?;
+        }
+        if let Some(window_duration) = smooth_window {
+            smooth_scores(&mut rows, window_duration)?;
+        }
+        let records = run_backtest(rows, w_mom, w_rev)?;
+
+        match output_format.as_str() {
+            "bin" => binfmt::write_bin(output_path, RecordKind::Pnl, &records)?,
+            "csv" => {
+                let output_file = File::create(Path::new(output_path))?;
+                let mut wtr = csv::Writer::from_writer(BufWriter::new(output_file));
+                wtr.write_record(["trade_date", "pnl", "equity"])?;
+                for record in &records {
+                    wtr.serialize((record.date_str(), record.pnl, record.equity))?;
+                }
+                wtr.flush()?;
+            }
+            other => return Err(format!("unsupported --output-format {other}").into()),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_std_uses_sample_variance() {
+        let (mu, sigma) = mean_std(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert!((mu - 5.0).abs() < 1e-9);
+        // Population std of this set is 2.0; sample std (n-1) is ~2.1381.
+        assert!((sigma - 2.1381).abs() < 1e-3);
+    }
+
+    #[test]
+    fn mean_std_single_sample_does_not_divide_by_zero() {
+        let (mu, sigma) = mean_std(&[3.0]);
+        assert_eq!(mu, 3.0);
+        assert_eq!(sigma, 0.0);
+    }
+
+    #[test]
+    fn zscore_handles_zero_sigma() {
+        assert_eq!(zscore(5.0, 5.0, 0.0), 0.0);
+        assert_eq!(zscore(5.0, 0.0, 2.5), 2.0);
+    }
+
+    #[test]
+    fn trade_date_epoch_secs_accepts_rfc3339_and_bare_date() {
+        let rfc3339 = trade_date_epoch_secs("2024-01-02T00:00:00Z").unwrap();
+        let bare = trade_date_epoch_secs("2024-01-02").unwrap();
+        assert_eq!(rfc3339, bare);
+    }
+
+    #[test]
+    fn trade_date_epoch_secs_rejects_garbage() {
+        assert!(trade_date_epoch_secs("not-a-date").is_err());
+    }
+
+    fn row(ticker: &str, trade_date: &str, mom: f64, rev: f64, price: f64) -> ScoredRow {
+        ScoredRow {
+            ticker: ticker.to_string(),
+            trade_date: trade_date.to_string(),
+            mom_score: mom,
+            rev_score: rev,
+            price,
+        }
+    }
+
+    #[test]
+    fn run_backtest_goes_long_top_quantile_and_short_bottom_quantile() {
+        // Five names on the rebalance date; AAA ranks lowest (short),
+        // EEE ranks highest (long). Prices double for longs' winners and
+        // halve for the would-be short, forward-marked on the next date.
+        let rows = vec![
+            row("AAA", "2024-01-01", -2.0, -2.0, 10.0),
+            row("BBB", "2024-01-01", -1.0, -1.0, 10.0),
+            row("CCC", "2024-01-01", 0.0, 0.0, 10.0),
+            row("DDD", "2024-01-01", 1.0, 1.0, 10.0),
+            row("EEE", "2024-01-01", 2.0, 2.0, 10.0),
+            row("AAA", "2024-01-02", 0.0, 0.0, 5.0),
+            row("BBB", "2024-01-02", 0.0, 0.0, 10.0),
+            row("CCC", "2024-01-02", 0.0, 0.0, 10.0),
+            row("DDD", "2024-01-02", 0.0, 0.0, 10.0),
+            row("EEE", "2024-01-02", 0.0, 0.0, 20.0),
+        ];
+
+        let records = run_backtest(rows, W_MOM, W_REV).unwrap();
+        // Only the first rebalance date has a forward period to mark to.
+        assert_eq!(records.len(), 1);
+
+        let record = &records[0];
+        assert_eq!(record.date_str(), "2024-01-01");
+        // Long EEE (+100%), short AAA (-50%): pnl = 1.0 - (-0.5) = 1.5.
+        assert!((record.pnl - 1.5).abs() < 1e-9);
+        assert!((record.equity - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn run_backtest_compounds_equity_across_periods() {
+        let rows = vec![
+            row("AAA", "2024-01-01", -1.0, -1.0, 10.0),
+            row("BBB", "2024-01-01", 1.0, 1.0, 10.0),
+            row("AAA", "2024-01-02", 0.0, 0.0, 10.0),
+            row("BBB", "2024-01-02", 0.0, 0.0, 20.0),
+            row("AAA", "2024-01-03", 0.0, 0.0, 10.0),
+            row("BBB", "2024-01-03", 0.0, 0.0, 10.0),
+        ];
+
+        let records = run_backtest(rows, W_MOM, W_REV).unwrap();
+        assert_eq!(records.len(), 2);
+        // Period 1: short AAA (flat), long BBB (+100%) -> pnl = 1.0, equity = 2.0.
+        assert!((records[0].pnl - 1.0).abs() < 1e-9);
+        assert!((records[0].equity - 2.0).abs() < 1e-9);
+        // Period 2: short AAA (flat), long BBB (-50%) -> pnl = -0.5, equity = 1.0.
+        assert!((records[1].pnl - (-0.5)).abs() < 1e-9);
+        assert!((records[1].equity - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use memmap2::Mmap;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Bumped whenever a record layout changes so old binary files are
+/// rejected instead of silently misread.
+const SCHEMA_VERSION: u32 = 1;
+
+/// Fixed-width string buffer, so every record encodes to the same number of
+/// bytes and can be indexed by offset without re-scanning the file. Wide
+/// enough for an RFC3339 timestamp with fractional seconds and a UTC offset
+/// (e.g. `2021-01-03T00:00:00.123456+00:00`, 32 bytes), which fills it exactly.
+const TICKER_LEN: usize = 16;
+const DATE_LEN: usize = 32;
+
+/// Which record type a binary file holds. `read_bin` checks this against
+/// the type it was asked to decode so a `PnlRecord` file (48B records)
+/// can't be fed in where a `ScoreRecord` file (72B records) is expected,
+/// or vice versa — a mismatch is rejected with an error naming both kinds.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecordKind {
+    Score,
+    Pnl,
+}
+
+/// Packs `s` into a fixed-size buffer, erroring instead of silently
+/// truncating when it doesn't fit.
+fn fixed_buf<const N: usize>(s: &str) -> Result<[u8; N], Box<dyn Error>> {
+    let bytes = s.as_bytes();
+    if bytes.len() > N {
+        return Err(format!("binfmt: {s:?} is {} bytes, longer than the {N}-byte fixed field", bytes.len()).into());
+    }
+    let mut buf = [0u8; N];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    Ok(buf)
+}
+
+fn buf_str<const N: usize>(buf: &[u8; N]) -> &str {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(N);
+    std::str::from_utf8(&buf[..end]).unwrap_or("")
+}
+
+/// One row of computed P&L, laid out for compact binary storage.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct PnlRecord {
+    pub date: [u8; DATE_LEN],
+    pub pnl: f64,
+    pub equity: f64,
+}
+
+impl PnlRecord {
+    pub fn new(date: &str, pnl: f64, equity: f64) -> Result<Self, Box<dyn Error>> {
+        Ok(PnlRecord {
+            date: fixed_buf(date)?,
+            pnl,
+            equity,
+        })
+    }
+
+    pub fn date_str(&self) -> &str {
+        buf_str(&self.date)
+    }
+}
+
+/// One pre-parsed `RustRow`, laid out for compact binary storage so a
+/// re-run over the same announcements can skip CSV tokenization and Serde
+/// string parsing entirely.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ScoreRecord {
+    pub ticker: [u8; TICKER_LEN],
+    pub trade_date: [u8; DATE_LEN],
+    pub mom_score: f64,
+    pub rev_score: f64,
+    pub price: f64,
+}
+
+impl ScoreRecord {
+    pub fn new(
+        ticker: &str,
+        trade_date: &str,
+        mom_score: f64,
+        rev_score: f64,
+        price: f64,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(ScoreRecord {
+            ticker: fixed_buf(ticker)?,
+            trade_date: fixed_buf(trade_date)?,
+            mom_score,
+            rev_score,
+            price,
+        })
+    }
+
+    pub fn ticker_str(&self) -> &str {
+        buf_str(&self.ticker)
+    }
+
+    pub fn trade_date_str(&self) -> &str {
+        buf_str(&self.trade_date)
+    }
+}
+
+/// `row_count` + `schema_version` + `kind`, written once up front so a
+/// reader knows how many fixed-size records follow, of what type, without
+/// scanning the file.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    schema_version: u32,
+    kind: RecordKind,
+    row_count: u64,
+}
+
+/// Serializes `records` to `path` as a small header followed by
+/// fixed-size bincode-encoded records.
+pub fn write_bin<T: Serialize>(path: &str, kind: RecordKind, records: &[T]) -> Result<(), Box<dyn Error>> {
+    let mut w = BufWriter::new(File::create(path)?);
+    let header = Header {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        row_count: records.len() as u64,
+    };
+    bincode::serialize_into(&mut w, &header)?;
+    for record in records {
+        bincode::serialize_into(&mut w, record)?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+/// Memory-maps `path` and decodes its fixed-size records directly out of the
+/// mapping (no intermediate file read into a buffer), reading the header
+/// once to recover the row count and validate both the schema version and
+/// the record kind. `record_size` is the bincode-encoded size of a single
+/// `T`; the mapped file's length must exactly match `header_size +
+/// row_count * record_size` or the file is rejected instead of read out of
+/// bounds. Each record is still bincode-decoded into an owned `T` (bincode
+/// has no borrowing decode path for these fixed types), so this saves the
+/// read() syscalls and the up-front whole-file buffer, not a per-record
+/// allocation.
+pub fn read_bin<T: DeserializeOwned>(
+    path: &str,
+    expected_kind: RecordKind,
+    record_size: usize,
+) -> Result<Vec<T>, Box<dyn Error>> {
+    let file = File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    let header_size = bincode::serialized_size(&Header {
+        schema_version: SCHEMA_VERSION,
+        kind: expected_kind,
+        row_count: 0,
+    })? as usize;
+    if mmap.len() < header_size {
+        return Err("binfmt: file is smaller than a header".into());
+    }
+    let header: Header = bincode::deserialize(&mmap[..header_size])?;
+    if header.schema_version != SCHEMA_VERSION {
+        return Err(format!(
+            "binfmt: unsupported schema version {} (expected {})",
+            header.schema_version, SCHEMA_VERSION
+        )
+        .into());
+    }
+    if header.kind != expected_kind {
+        return Err(format!(
+            "binfmt: file holds {:?} records, expected {:?}",
+            header.kind, expected_kind
+        )
+        .into());
+    }
+
+    let expected_len = header_size + header.row_count as usize * record_size;
+    if mmap.len() != expected_len {
+        return Err(format!(
+            "binfmt: file is {} bytes, expected {} for {} {:?} records",
+            mmap.len(),
+            expected_len,
+            header.row_count,
+            header.kind
+        )
+        .into());
+    }
+
+    let mut records = Vec::with_capacity(header.row_count as usize);
+    for i in 0..header.row_count as usize {
+        let start = header_size + i * record_size;
+        let end = start + record_size;
+        records.push(bincode::deserialize(&mmap[start..end])?);
+    }
+    Ok(records)
+}
@@ -0,0 +1,38 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::BufWriter,
+    path::Path,
+};
+
+use crate::binfmt::{self, PnlRecord, RecordKind};
+
+/// Entry point for the `convert` subcommand: `convert --input <pnl.bin>
+/// --output <out.csv>`.
+///
+/// Reads back a `--output-format bin` file of `PnlRecord`s (written by the
+/// `backtest` subcommand) and reformats it as CSV, giving the binary P&L
+/// output an actual reader rather than a write-only format.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let input_path = flag(args, "--input").ok_or("convert requires --input <pnl.bin>")?;
+    let output_path = flag(args, "--output").ok_or("convert requires --output <out.csv>")?;
+
+    let record_size = bincode::serialized_size(&PnlRecord::new("", 0.0, 0.0)?)? as usize;
+    let records = binfmt::read_bin::<PnlRecord>(input_path, RecordKind::Pnl, record_size)?;
+
+    let output_file = File::create(Path::new(output_path))?;
+    let mut wtr = csv::Writer::from_writer(BufWriter::new(output_file));
+    wtr.write_record(["trade_date", "pnl", "equity"])?;
+    for record in &records {
+        wtr.serialize((record.date_str(), record.pnl, record.equity))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
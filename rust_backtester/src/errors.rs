@@ -0,0 +1,101 @@
+use std::{error::Error, fmt};
+
+/// How a malformed row should be handled: `Fail` (the default) aborts the
+/// run on the first bad row, `Skip` counts it and keeps going.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum OnError {
+    Fail,
+    Skip,
+}
+
+impl OnError {
+    /// Parses a `--on-error {skip,fail}` flag out of the trailing args,
+    /// defaulting to `Fail`.
+    pub fn parse(args: &[String]) -> Result<Self, Box<dyn Error>> {
+        match args
+            .iter()
+            .position(|a| a == "--on-error")
+            .and_then(|i| args.get(i + 1))
+            .map(String::as_str)
+        {
+            Some("skip") => Ok(OnError::Skip),
+            Some("fail") | None => Ok(OnError::Fail),
+            Some(other) => Err(format!("--on-error expects skip or fail, got {other}").into()),
+        }
+    }
+}
+
+/// Which half of the tool hit the parse failure, so the context line tells
+/// you which mode (and therefore which row shape) was expected.
+#[derive(Clone, Copy)]
+pub enum Mode {
+    Replay,
+    Pnl,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mode::Replay => write!(f, "replay"),
+            Mode::Pnl => write!(f, "p&l"),
+        }
+    }
+}
+
+/// Wraps a raw `csv::Error` with the 1-based record number, the offending
+/// record's raw contents, and which mode was parsing it, so a bad row deep
+/// in a multi-gigabyte file can actually be tracked down.
+#[derive(Debug)]
+pub struct RowError {
+    pub record_number: u64,
+    pub mode: String,
+    pub raw_record: String,
+    pub source: csv::Error,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "row {} ({} mode) failed to parse: {} (raw record: {:?})",
+            self.record_number, self.mode, self.source, self.raw_record
+        )
+    }
+}
+
+impl Error for RowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl RowError {
+    pub fn new(record_number: u64, mode: Mode, raw_record: String, source: csv::Error) -> Self {
+        RowError {
+            record_number,
+            mode: mode.to_string(),
+            raw_record,
+            source,
+        }
+    }
+}
+
+/// Tracks how many rows were skipped under `--on-error skip` and prints a
+/// one-line summary at the end of the run.
+#[derive(Default)]
+pub struct SkipCounter {
+    pub skipped: u64,
+}
+
+impl SkipCounter {
+    pub fn record(&mut self, err: &RowError) {
+        self.skipped += 1;
+        eprintln!("skipping: {err}");
+    }
+
+    pub fn report(&self) {
+        if self.skipped > 0 {
+            eprintln!("done: skipped {} malformed row(s)", self.skipped);
+        }
+    }
+}
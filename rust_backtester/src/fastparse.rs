@@ -0,0 +1,66 @@
+use std::{error::Error, time::Instant};
+
+/// How often (in rows) to emit a progress line to stderr.
+pub const PROGRESS_EVERY: u64 = 2_000_000;
+
+/// Tracks elapsed time and throughput across a long-running scan, printing a
+/// line to stderr every [`PROGRESS_EVERY`] rows so multi-gigabyte runs stay
+/// visibly alive.
+pub struct Progress {
+    label: &'static str,
+    started: Instant,
+    rows: u64,
+}
+
+impl Progress {
+    pub fn new(label: &'static str) -> Self {
+        Progress {
+            label,
+            started: Instant::now(),
+            rows: 0,
+        }
+    }
+
+    /// Call once per row processed; prints a progress line every
+    /// `PROGRESS_EVERY` rows.
+    pub fn tick(&mut self) {
+        self.rows += 1;
+        if self.rows.is_multiple_of(PROGRESS_EVERY) {
+            self.report();
+        }
+    }
+
+    fn report(&self) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = if elapsed > 0.0 {
+            self.rows as f64 / elapsed
+        } else {
+            0.0
+        };
+        eprintln!(
+            "[{}] {} rows in {:.1}s ({:.0} rows/sec)",
+            self.label, self.rows, elapsed, rate
+        );
+    }
+
+    /// Call once after the scan finishes to print a final summary line.
+    pub fn finish(&self) {
+        self.report();
+    }
+}
+
+/// Finds the column index of `name` among a header `ByteRecord`.
+pub fn find_column(headers: &csv::ByteRecord, name: &str) -> Option<usize> {
+    headers.iter().position(|h| h == name.as_bytes())
+}
+
+/// Parses the `f64` at `col` directly out of a `ByteRecord`, skipping the
+/// full `StringRecord`/Serde deserialization path. This is the fast path for
+/// REPLAY MODE, where `portfolio` is the only column that matters.
+pub fn parse_f64_column(record: &csv::ByteRecord, col: usize) -> Result<f64, Box<dyn Error>> {
+    let field = record
+        .get(col)
+        .ok_or_else(|| format!("record has no column {col}"))?;
+    let s = std::str::from_utf8(field)?;
+    Ok(s.parse::<f64>()?)
+}
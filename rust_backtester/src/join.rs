@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+/// Options for the `join` subcommand, parsed from its flags.
+pub struct JoinArgs {
+    pub left_path: String,
+    pub right_path: String,
+    pub output_path: String,
+    pub left_key_cols: Vec<String>,
+    pub right_key_cols: Vec<String>,
+    pub left_join: bool,
+    pub delimiter: u8,
+}
+
+/// Parses `join --left <f> --right <f> --output <f> [--left-key col,col]
+/// [--right-key col,col] [--left-join] [--delimiter <char>]`.
+pub fn parse_args(args: &[String]) -> Result<JoinArgs, Box<dyn Error>> {
+    let mut left_path = None;
+    let mut right_path = None;
+    let mut output_path = None;
+    let mut left_key_cols = vec!["Ticker".to_string(), "TradeDate".to_string()];
+    let mut right_key_cols = vec!["Ticker".to_string(), "TradeDate".to_string()];
+    let mut left_join = false;
+    let mut delimiter = b',';
+
+    // Fetches the operand following a flag, erroring out instead of
+    // panicking when the flag is the last argument.
+    let operand = |args: &[String], i: usize, flag: &str| -> Result<String, Box<dyn Error>> {
+        args.get(i + 1)
+            .cloned()
+            .ok_or_else(|| format!("join: {flag} requires a value").into())
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--left" => {
+                left_path = Some(operand(args, i, "--left")?);
+                i += 2;
+            }
+            "--right" => {
+                right_path = Some(operand(args, i, "--right")?);
+                i += 2;
+            }
+            "--output" => {
+                output_path = Some(operand(args, i, "--output")?);
+                i += 2;
+            }
+            "--left-key" => {
+                left_key_cols = operand(args, i, "--left-key")?
+                    .split(',')
+                    .map(str::to_string)
+                    .collect();
+                i += 2;
+            }
+            "--right-key" => {
+                right_key_cols = operand(args, i, "--right-key")?
+                    .split(',')
+                    .map(str::to_string)
+                    .collect();
+                i += 2;
+            }
+            "--left-join" => {
+                left_join = true;
+                i += 1;
+            }
+            "--delimiter" => {
+                let value = operand(args, i, "--delimiter")?;
+                delimiter = *value
+                    .as_bytes()
+                    .first()
+                    .ok_or("join: --delimiter requires a non-empty value")?;
+                i += 2;
+            }
+            other => return Err(format!("join: unrecognized flag {other}").into()),
+        }
+    }
+
+    Ok(JoinArgs {
+        left_path: left_path.ok_or("join requires --left <announcements.csv>")?,
+        right_path: right_path.ok_or("join requires --right <prices.csv>")?,
+        output_path: output_path.ok_or("join requires --output <out.csv>")?,
+        left_key_cols,
+        right_key_cols,
+        left_join,
+        delimiter,
+    })
+}
+
+/// `(key tuple) -> row)` lookup built from the right-hand (prices) CSV.
+type RightIndex = HashMap<Vec<String>, csv::StringRecord>;
+
+/// Builds the `(key tuple) -> row)` lookup for the right-hand (prices) CSV.
+fn build_right_index(
+    rdr: &mut csv::Reader<BufReader<File>>,
+    right_key_cols: &[String],
+) -> Result<(csv::StringRecord, RightIndex), Box<dyn Error>> {
+    let headers = rdr.headers()?.clone();
+    let key_positions: Vec<usize> = right_key_cols
+        .iter()
+        .map(|col| {
+            headers
+                .iter()
+                .position(|h| h == col)
+                .ok_or_else(|| format!("join: right CSV is missing key column {col}"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut index = HashMap::new();
+    for result in rdr.records() {
+        let record = result?;
+        let key: Vec<String> = key_positions.iter().map(|&p| record[p].to_string()).collect();
+        index.insert(key, record);
+    }
+    Ok((headers, index))
+}
+
+/// Hash-joins `left_path` against `right_path` on `left_key_cols`/
+/// `right_key_cols`, writing the concatenated record per matched (or, under
+/// `--left-join`, unmatched) left row to `output_path`.
+pub fn run(join_args: JoinArgs) -> Result<(), Box<dyn Error>> {
+    let mut right_rdr = csv::ReaderBuilder::new()
+        .delimiter(join_args.delimiter)
+        .from_reader(BufReader::new(File::open(Path::new(&join_args.right_path))?));
+    let (right_headers, right_index) = build_right_index(&mut right_rdr, &join_args.right_key_cols)?;
+
+    let mut left_rdr = csv::ReaderBuilder::new()
+        .delimiter(join_args.delimiter)
+        .from_reader(BufReader::new(File::open(Path::new(&join_args.left_path))?));
+    let left_headers = left_rdr.headers()?.clone();
+    let left_key_positions: Vec<usize> = join_args
+        .left_key_cols
+        .iter()
+        .map(|col| {
+            left_headers
+                .iter()
+                .position(|h| h == col)
+                .ok_or_else(|| format!("join: left CSV is missing key column {col}"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(join_args.delimiter)
+        .from_writer(BufWriter::new(File::create(Path::new(&join_args.output_path))?));
+
+    let mut out_header: Vec<String> = left_headers.iter().map(str::to_string).collect();
+    out_header.extend(right_headers.iter().map(str::to_string));
+    wtr.write_record(&out_header)?;
+
+    let empty_right_fields = vec![String::new(); right_headers.len()];
+    for result in left_rdr.records() {
+        let left_record = result?;
+        let key: Vec<String> = left_key_positions
+            .iter()
+            .map(|&p| left_record[p].to_string())
+            .collect();
+
+        match right_index.get(&key) {
+            Some(right_record) => {
+                let mut out_record: Vec<&str> = left_record.iter().collect();
+                out_record.extend(right_record.iter());
+                wtr.write_record(&out_record)?;
+            }
+            None if join_args.left_join => {
+                let mut out_record: Vec<&str> = left_record.iter().collect();
+                out_record.extend(empty_right_fields.iter().map(String::as_str));
+                wtr.write_record(&out_record)?;
+            }
+            None => {
+                // Inner join: unmatched left rows are dropped.
+            }
+        }
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
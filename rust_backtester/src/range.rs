@@ -0,0 +1,64 @@
+use std::{
+    error::Error,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+
+/// Entry point for the `range` subcommand: `range --input <in.csv> --output
+/// <out.csv> --start <rfc3339> --end <rfc3339>`.
+///
+/// Streams `input`, assumed sorted ascending by `TradeDate`, and passes
+/// through only rows whose date falls within `[start, end]`. Because the
+/// input is known-sorted, this stops reading at the first row past `end`
+/// instead of scanning the whole file — the walk-forward / in-sample /
+/// out-of-sample use case this exists for is usually run against a single
+/// master file many times over, so that early-out matters.
+pub fn run(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let input_path = flag(args, "--input").ok_or("range requires --input <in.csv>")?;
+    let output_path = flag(args, "--output").ok_or("range requires --output <out.csv>")?;
+    let start: DateTime<Utc> = flag(args, "--start")
+        .ok_or("range requires --start <rfc3339>")?
+        .parse()?;
+    let end: DateTime<Utc> = flag(args, "--end")
+        .ok_or("range requires --end <rfc3339>")?
+        .parse()?;
+
+    let input_file = File::open(Path::new(&input_path))?;
+    let mut rdr = csv::Reader::from_reader(BufReader::new(input_file));
+    let headers = rdr.headers()?.clone();
+    let trade_date_col = headers
+        .iter()
+        .position(|h| h == "TradeDate")
+        .ok_or("range: input CSV is missing a TradeDate column")?;
+
+    let output_file = File::create(Path::new(&output_path))?;
+    let mut wtr = csv::Writer::from_writer(BufWriter::new(output_file));
+    wtr.write_record(&headers)?;
+
+    for result in rdr.records() {
+        let record = result?;
+        let trade_date: DateTime<Utc> = record[trade_date_col].parse()?;
+        if trade_date < start {
+            continue;
+        }
+        if trade_date > end {
+            // Input is sorted ascending by TradeDate, so nothing past this
+            // row can be in range either.
+            break;
+        }
+        wtr.write_record(&record)?;
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
+
+fn flag<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
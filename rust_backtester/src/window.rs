@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+/// A single weighted sample fed into a [`WeightedMeanWindow`].
+struct Sample {
+    ts: f64,
+    value: f64,
+    weight: f64,
+}
+
+/// A sliding time-window weighted mean over `(timestamp, value, weight)`
+/// samples, used to smooth the `mom_score`/`rev_score` streams before they
+/// feed the backtest's composite signal.
+///
+/// Keeps a `VecDeque` of in-window samples plus running accumulators so
+/// `mean()` is O(1) regardless of window size.
+pub struct WeightedMeanWindow {
+    window_duration: f64,
+    samples: VecDeque<Sample>,
+    sum_wv: f64,
+    sum_w: f64,
+}
+
+impl WeightedMeanWindow {
+    /// Creates a window that retains samples within `window_duration` of the
+    /// most recently pushed timestamp.
+    pub fn new(window_duration: f64) -> Self {
+        WeightedMeanWindow {
+            window_duration,
+            samples: VecDeque::new(),
+            sum_wv: 0.0,
+            sum_w: 0.0,
+        }
+    }
+
+    /// Pushes a new sample and evicts anything that has fallen out of the
+    /// window, updating the running accumulators as it goes.
+    pub fn push(&mut self, ts: f64, value: f64, weight: f64) {
+        self.samples.push_back(Sample { ts, value, weight });
+        self.sum_wv += weight * value;
+        self.sum_w += weight;
+
+        while let Some(front) = self.samples.front() {
+            if ts - front.ts > self.window_duration {
+                self.sum_wv -= front.weight * front.value;
+                self.sum_w -= front.weight;
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// The current weighted mean, or `NaN` if the window is empty.
+    pub fn mean(&self) -> f64 {
+        if self.sum_w == 0.0 {
+            f64::NAN
+        } else {
+            self.sum_wv / self.sum_w
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mean_is_nan_when_empty() {
+        let window = WeightedMeanWindow::new(10.0);
+        assert!(window.mean().is_nan());
+    }
+
+    #[test]
+    fn mean_is_unweighted_average_with_unit_weights() {
+        let mut window = WeightedMeanWindow::new(10.0);
+        window.push(0.0, 2.0, 1.0);
+        window.push(1.0, 4.0, 1.0);
+        assert!((window.mean() - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn mean_respects_sample_weights() {
+        let mut window = WeightedMeanWindow::new(10.0);
+        window.push(0.0, 0.0, 1.0);
+        window.push(1.0, 10.0, 3.0);
+        // (0*1 + 10*3) / (1 + 3) = 7.5
+        assert!((window.mean() - 7.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn evicts_samples_older_than_window_duration() {
+        let mut window = WeightedMeanWindow::new(8.0);
+        window.push(0.0, 100.0, 1.0);
+        window.push(3.0, 100.0, 1.0);
+        // ts=10 is 10s after ts=0, which is outside the 8s window, so the
+        // first sample is evicted; ts=3 (7s old) is still in range.
+        window.push(10.0, 20.0, 1.0);
+        assert!((window.mean() - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn boundary_sample_is_kept_not_evicted() {
+        let mut window = WeightedMeanWindow::new(5.0);
+        window.push(0.0, 42.0, 1.0);
+        // Exactly at the window edge (ts - front.ts == window_duration)
+        // should be retained since eviction is a strict `>` comparison.
+        window.push(5.0, 42.0, 1.0);
+        assert!((window.mean() - 42.0).abs() < 1e-9);
+    }
+}